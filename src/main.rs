@@ -1,4 +1,12 @@
-use std::rc::Rc;
+// Every example in this file is a standalone lesson you run by calling it from `main` yourself
+// (see the comment there), so most of them are never called in the committed source. Allow
+// dead code crate-wide rather than peppering every function and type with its own annotation.
+#![allow(dead_code)]
+
+use std::cell::{RefCell, UnsafeCell};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
 
 fn first_example() {
     // Rust has two string types: `str` (the primitive) and `String`, which wraps `str`.
@@ -30,10 +38,16 @@ fn first_example() {
         prefix
     }
 
-    // Observe the compiler error!
+    // Uncomment to observe the compiler error! It's reproduced as-is in
+    // tests/compile_fail/first_example_use_after_move.rs, so you can see it fail without
+    // breaking this function's ability to compile and run.
     // How can you change the signature of concat_strings to get this to compile?
     // You should listen to its suggestion regarding the function's signature for better performance.
-    let new_s = concat_strings(s, ". ", t);
+    // let new_s = concat_strings(s, ". ", t);
+    // println!("Mapped \"{s}\" and \"{t}\" into \"{new_s}\"");
+
+    // Cloning what you still need after the move is the straightforward fix.
+    let new_s = concat_strings(s.clone(), ". ", t.clone());
     println!("Mapped \"{s}\" and \"{t}\" into \"{new_s}\"");
 
     assert_eq!(new_s, other_concat_strings(s, ". ", t));
@@ -55,6 +69,35 @@ impl BunchaData {
             curr: 0,
         }
     }
+
+    fn bump_curr(&mut self) {
+        self.curr += 1;
+    }
+
+    // The compiler doesn't look inside `bump_curr` to see that it only touches `self.curr`.
+    // `&mut self` borrows *all* of `BunchaData`, so a live borrow of any one field -- `self.s`
+    // here -- conflicts with any method call on `self`, even one that would never touch `s`.
+    fn borrow_field_then_mutate(&mut self) {
+        let s_ref = &mut self.s;
+
+        // Uncomment to cause a compilation error. `bump_curr` takes `&mut self`, which the
+        // borrow checker must treat as reborrowing the whole struct -- including the `s`
+        // that `s_ref` is still holding onto.
+        // self.bump_curr();
+
+        s_ref.push_str(" (borrowed)");
+        println!("s_ref = {s_ref}");
+    }
+
+    // Destructuring `self` in one expression sidesteps the whole-struct borrow above: the
+    // compiler can see, right here, that `s`, `t`, and `curr` are disjoint fields, so it hands
+    // back three independent `&mut` borrows instead of one big one covering the struct. This
+    // is the idiomatic fix whenever you need simultaneous mutable access to several fields and
+    // method-by-method borrowing is too coarse to express it.
+    fn split_mut(&mut self) -> (&mut String, &mut Vec<usize>, &mut usize) {
+        let Self { s, t, curr } = self;
+        (s, t, curr)
+    }
 }
 
 fn structs_automove_too() {
@@ -62,12 +105,22 @@ fn structs_automove_too() {
     let t = vec![4; 10];
 
     // No (deep) copies occur in the construction of `clump.`
-    let clump = BunchaData { s, t, curr: 0 };
+    let clump = BunchaData {
+        s: s.clone(),
+        t: t.clone(),
+        curr: 0,
+    };
     // Once again, we observe the same behavior.
     // This time, there is no function signature to change.
     // You either only access `s` through `clump` or you copy `s`.
     println!("I built {clump:?} using {s} and {t:?}");
 
+    // Uncomment to observe the compiler error. It's reproduced as-is (minus the `.clone()`s
+    // above) in tests/compile_fail/structs_automove_too_use_after_move.rs, so you can see it
+    // fail without breaking this function's ability to compile and run.
+    // let clump2 = BunchaData { s, t, curr: 0 };
+    // println!("I built {clump2:?} using {s} and {t:?}");
+
     let mut t2 = vec![2, 3, 5, 7, 11];
     t2.push(13);
     // As previously established, t2 is not deep-copied for this function call.
@@ -76,6 +129,23 @@ fn structs_automove_too() {
     println!("Here's my other clump: {other_clump:?}");
 }
 
+fn disjoint_field_borrows() {
+    let mut clump = BunchaData::with_empty_string(vec![1, 2, 3]);
+    clump.borrow_field_then_mutate();
+
+    // Calling `bump_curr` here is fine: the borrow from `borrow_field_then_mutate` already
+    // ended when that call returned, so `&mut clump` is free again.
+    clump.bump_curr();
+
+    // `split_mut` hands back three independent borrows in one shot, so we can hold all of
+    // them at once -- something no amount of separate method calls on `&mut clump` could do.
+    let (s, t, curr) = clump.split_mut();
+    s.push('!');
+    t.push(*curr);
+    *curr += 1;
+    println!("s = {s}, t = {t:?}, curr = {curr}");
+}
+
 fn auto_copy() {
     let x = 1;
     let y = 2;
@@ -169,6 +239,39 @@ fn borrows_and_their_lifetimes() {
     println!("Introducing the new values of x and y: x = {x}, y = {y}");
 }
 
+// NLL (above) figured out that a borrow's live range can end before its lexical scope does.
+// It still isn't perfect: NLL decides a borrow's liveness from the region of code reachable
+// from its creation, without reasoning about *which* control-flow path was actually taken to
+// reach a given use. This is the textbook gap Polonius is meant to close by tracking borrows
+// as facts over the control-flow graph instead of as single regions, so it can see that the
+// two branches below never both need the borrow alive at once.
+fn nll_conditional_return_limitation() {
+    let mut map: HashMap<i32, i32> = HashMap::new();
+
+    // Uncomment to cause a compilation error. Looking up `key` in the `match` creates a
+    // mutable borrow of `map` that has to live as long as the value it returns, since that
+    // value might be `Some(value)` borrowed straight out of the map. But on the `None` path,
+    // nothing is borrowed from the first lookup anymore -- and NLL still can't tell the two
+    // branches apart, so the second `&mut map` in `None` is rejected as a conflicting borrow
+    // even though the first one is logically dead by then.
+    // fn get_default<'m>(map: &'m mut HashMap<i32, i32>, key: i32) -> &'m mut i32 {
+    //     match map.get_mut(&key) {
+    //         Some(value) => value,
+    //         None => {
+    //             map.insert(key, 0);
+    //             map.get_mut(&key).unwrap()
+    //         }
+    //     }
+    // }
+
+    // The two-lookup version below is the idiomatic workaround: restructure so there's only
+    // ever one live mutable borrow in flight, at the cost of a redundant second hash lookup.
+    map.entry(1).or_insert(0);
+    *map.get_mut(&1).unwrap() += 10;
+
+    println!("map = {map:?}");
+}
+
 // Storing references in structs requires that you
 // specify how long they live in relation to the struct.
 // This is because the memory that the reference is pointing to is not a part of the struct itself,
@@ -185,6 +288,234 @@ struct TwoLifeTimes<'a, 'b> {
     slice: &'b mut &'a str,
 }
 
+// So far every example of "aliasable XOR mutable" has been enforced by the borrow checker
+// directly on the value itself. GhostCell shows you can move that enforcement onto a
+// *separate* value (the token) and let ordinary borrow-checking of the token stand in for
+// borrow-checking of however many cells you like.
+//
+// `'brand` doesn't name a real region of memory. It exists purely so the compiler can refuse
+// to unify tokens that weren't created together. For that trick to work, `'brand` has to be
+// invariant: if it were covariant, the compiler could shrink two different `'brand`s down to
+// some common sub-lifetime and let you mix tokens that should be kept apart.
+// `PhantomData<fn(&'brand ()) -> &'brand ()>` is the standard way to ask for invariance without
+// storing anything at runtime: function pointers are contravariant in their arguments and
+// covariant in their return type, and using the same lifetime in both positions cancels out
+// any wiggle room in either direction.
+struct GhostToken<'brand> {
+    _marker: PhantomData<fn(&'brand ()) -> &'brand ()>,
+}
+
+impl<'brand> GhostToken<'brand> {
+    // There is deliberately no public constructor. The only way to get a `GhostToken` is to
+    // ask for one inside this closure, which picks a fresh `'brand` that can't have leaked out
+    // to collide with anyone else's. `for<'brand_inner>` below is what makes the brand fresh: the
+    // caller's closure must work for *every* lifetime, so it can't smuggle in one it already
+    // had lying around.
+    //
+    // This returns the closure's result rather than `Self` by design -- the whole point is
+    // that a bare `GhostToken` never escapes on its own, only through the closure's return
+    // value -- so the `new_ret_no_self` lint doesn't apply here.
+    #[allow(clippy::new_ret_no_self)]
+    fn new<R>(f: impl for<'brand_inner> FnOnce(GhostToken<'brand_inner>) -> R) -> R {
+        f(GhostToken {
+            _marker: PhantomData,
+        })
+    }
+}
+
+// The cell itself is just an `UnsafeCell` tagged with the brand of the token that's allowed
+// to unlock it. No synchronization, no runtime checks: the borrow rules on `&GhostToken` and
+// `&mut GhostToken` are doing all the work, for every `GhostCell` that shares the same brand.
+struct GhostCell<'brand, T> {
+    value: UnsafeCell<T>,
+    _marker: PhantomData<fn(&'brand ()) -> &'brand ()>,
+}
+
+impl<'brand, T> GhostCell<'brand, T> {
+    fn new(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            _marker: PhantomData,
+        }
+    }
+
+    // Safe because producing this `&T` required only `&token`, and the only way to get a
+    // `&mut T` out of any cell branded `'brand` is through `&mut token` -- which the borrow
+    // checker guarantees can't coexist with this borrow.
+    fn borrow<'a>(&'a self, _token: &'a GhostToken<'brand>) -> &'a T {
+        unsafe { &*self.value.get() }
+    }
+
+    // Safe for the same reason, mirrored: the `&mut token` this takes excludes every other
+    // borrow of the token, and therefore every other access to every cell sharing this brand.
+    fn borrow_mut<'a>(&'a self, token: &'a mut GhostToken<'brand>) -> &'a mut T {
+        let _ = token;
+        unsafe { &mut *self.value.get() }
+    }
+}
+
+fn ghost_cell_branding() {
+    GhostToken::new(|mut token| {
+        let a = GhostCell::new(1);
+        let b = GhostCell::new(2);
+
+        // Many shared borrows through `&token` are fine, even across two different cells.
+        let ra = a.borrow(&token);
+        let rb = b.borrow(&token);
+        println!("a = {ra}, b = {rb}");
+
+        // Mutating either cell needs `&mut token`, which the borrow checker only hands out
+        // once `ra`/`rb` are done being used. Because both cells answer to the same token,
+        // this one `&mut` is enough to rule out *all* other access to *both* cells at once --
+        // that's the XOR guarantee, scaled up from one value to an arbitrarily large set.
+        *a.borrow_mut(&mut token) += 10;
+        println!("a is now {}", a.borrow(&token));
+
+        // Uncomment to cause a compilation error: `token2` has a different, unrelated brand,
+        // because invariance forbids the compiler from unifying `'brand` with `'brand2` even
+        // though nothing about the *values* involved looks incompatible.
+        // GhostToken::new(|token2| {
+        //     let _ = a.borrow(&token2);
+        // });
+    });
+}
+
+// A tagged union like `Foo` packs three colliding design choices into one value: the payload
+// lives inline right next to the tag, the tag can be overwritten (it's a plain `let mut`, not
+// a `const`), and we're about to hand out a pointer *into* the payload. Any language that
+// allows all three at once has a safety hole: write through the interior pointer, then
+// overwrite the tag underneath it, and the pointer now reads the wrong variant's bytes as if
+// they were the right one.
+enum Foo {
+    A(u32),
+    B(f64),
+}
+
+fn tagged_union_problem() {
+    let mut x = Foo::B(2.0);
+
+    // `y` borrows the `f64` payload living inside `x`'s `B` variant.
+    if let Foo::B(ref mut y) = x {
+        // Uncomment to cause a compilation error. If this were allowed, `x` would now be an
+        // `A(u32)` -- the tag says so -- but `y` still points at the same bytes and would let
+        // you write a `u32` through them. Read `*y` afterwards and you'd get a `u32`'s bit
+        // pattern reinterpreted as an `f64`: the exact miscompile tagged unions are famous for
+        // in languages without this check.
+        // x = Foo::A(7);
+
+        *y += 1.0;
+        println!("y = {y}");
+    }
+}
+
+// You might expect passing `&mut T` into a function to move it, the same way `first_example`
+// moved `String`s. It doesn't, but not because references are `Copy`: shared references are,
+// but `&mut T` deliberately isn't -- copying a `&mut T` would give you two mutable pointers to
+// the same data, exactly what the borrow checker exists to forbid. So instead the compiler
+// *reborrows* it -- it creates a new, shorter-lived `&mut T` for the callee to use, and the
+// original binding picks back up once that reborrow's last use is over. Nothing is consumed;
+// the original mutable reference is just temporarily "lent out."
+fn passthrough(r: &mut u8) -> &mut u8 {
+    r
+}
+
+struct Thing {
+    a: u8,
+}
+
+fn field_of(t: &mut Thing) -> &mut u8 {
+    &mut t.a
+}
+
+fn reborrowing() {
+    let mut x = 5u8;
+    let y = &mut x;
+    // `y` is reborrowed here, not moved: `passthrough` receives a fresh `&mut u8` that lives
+    // only as long as `z` needs it.
+    let z = passthrough(y);
+
+    // Uncomment to cause a compilation error. Even though `passthrough` only reborrowed `y`,
+    // the reborrow's lifetime is tied to `z`'s, and `z` is still live here, so `*y` (and `y`
+    // itself) stays borrowed until `z`'s last use.
+    // println!("{y}");
+
+    *z += 1;
+    println!("z = {z}");
+
+    let mut t = Thing { a: 1 };
+    let fa = &mut t;
+    // The same rule applies one level deeper: `field_of` returns a reference into one field,
+    // but because that reference was derived from `&mut t`, the compiler can't statically
+    // prove the rest of `t` is untouched -- so the *whole* struct stays borrowed through `fb`.
+    let fb = field_of(fa);
+    *fb += 1;
+    println!("t.a = {fb}");
+}
+
+// Everything so far has been data the ownership model can express on its own: trees, chains,
+// the occasional reference with a lifetime. A graph with a cycle in it -- like a parent that
+// needs to reach its children and children that need to reach back up to their parent -- can't
+// have a single owner anywhere in the cycle, so `Rc<RefCell<T>>` steps in: `Rc` gives shared
+// ownership, `RefCell` moves the aliasable-but-mutable check from compile time to run time
+// (panicking on conflicting borrows instead of refusing to compile).
+struct Node {
+    value: i32,
+    parent: Option<Weak<RefCell<Node>>>,
+    children: Vec<Rc<RefCell<Node>>>,
+}
+
+impl Node {
+    fn new(value: i32) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            value,
+            parent: None,
+            children: Vec::new(),
+        }))
+    }
+}
+
+fn shared_mutable_graph() {
+    let parent = Node::new(1);
+    let child = Node::new(2);
+
+    // The child points back up to the parent, but only weakly: `Weak` doesn't keep `parent`
+    // alive, so this back-edge can't stop `parent` from being dropped once nothing strong
+    // points to it anymore.
+    child.borrow_mut().parent = Some(Rc::downgrade(&parent));
+    parent.borrow_mut().children.push(Rc::clone(&child));
+
+    // Mutating a node that's shared via `Rc` has to go through `RefCell::borrow_mut`, which
+    // enforces XOR at runtime instead of compile time: this borrow would panic if another
+    // `borrow`/`borrow_mut` on the same `RefCell` were still alive.
+    parent.borrow_mut().value += 10;
+
+    // `upgrade` turns the `Weak` back into a real `Rc`, but only succeeds while the pointee is
+    // still alive -- here that's guaranteed, since `parent` is in scope.
+    let parent_value = child
+        .borrow()
+        .parent
+        .as_ref()
+        .and_then(Weak::upgrade)
+        .map(|p| p.borrow().value)
+        .unwrap();
+    println!(
+        "child {} sees parent value {parent_value}",
+        child.borrow().value
+    );
+
+    // The commented-out `LeakyNode` below shows the leak this is avoiding: if the back-edge
+    // were `Rc<RefCell<Node>>` instead of `Weak`, `parent` and `child` would hold strong
+    // references to each other.
+    // Neither strong count would ever drop to zero on its own, so neither node's destructor
+    // would run even after both `parent` and `child` go out of scope here -- a classic
+    // reference-cycle leak that `Weak` sidesteps by never contributing to the strong count.
+    // struct LeakyNode {
+    //     value: i32,
+    //     parent: Option<Rc<RefCell<LeakyNode>>>,
+    //     children: Vec<Rc<RefCell<LeakyNode>>>,
+    // }
+}
+
 fn main() {
     // To see the runtime behavior of any of the above,
     // simply call them right here!
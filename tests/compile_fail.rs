@@ -0,0 +1,9 @@
+// Pins down the "Uncomment to cause compilation error" comments scattered through
+// `src/main.rs` so the demo's pedagogy doesn't silently bit-rot as the compiler evolves.
+// Each fixture under `tests/compile_fail/` is a minimal, self-contained reproduction of one
+// of those errors, with its expected rustc diagnostic recorded alongside it.
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+}
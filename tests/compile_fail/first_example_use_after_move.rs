@@ -0,0 +1,17 @@
+// Reproduces the move error from `first_example` in src/main.rs: `concat_strings` consumes
+// both of its `String` arguments, so using `s` or `t` again afterwards is a use-after-move.
+fn concat_strings(prefix: String, between: &str, suffix: String) -> String {
+    prefix
+        .chars()
+        .chain(between.chars())
+        .chain(suffix.chars())
+        .collect()
+}
+
+fn main() {
+    let s = String::from("Howdy, Sailor");
+    let t = String::from("Did you statically check the scallywag?");
+
+    let new_s = concat_strings(s, ". ", t);
+    println!("Mapped \"{s}\" and \"{t}\" into \"{new_s}\"");
+}
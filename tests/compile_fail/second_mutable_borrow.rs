@@ -0,0 +1,16 @@
+// Reproduces the commented-out `let my2 = &mut y;` in `borrows_and_their_lifetimes` in
+// src/main.rs: `my` is still live when `my2` is created (it's used later in `swap`), and two
+// live `&mut` borrows of the same value is exactly what the borrow checker's XOR rule forbids.
+use std::mem::swap;
+
+fn main() {
+    let mut x = 10;
+    let mut y = 20;
+    let my = &mut y;
+    let my2 = &mut y;
+
+    let mx = &mut x;
+    swap(mx, my);
+    println!("x = {x}");
+    let _ = my2;
+}
@@ -0,0 +1,17 @@
+// Reproduces the move error from `structs_automove_too` in src/main.rs: building `clump` moves
+// `s` and `t` into it, so using them again afterwards is a use-after-move, same as passing
+// them to a function would be.
+#[derive(Debug)]
+struct BunchaData {
+    s: String,
+    t: Vec<usize>,
+    curr: usize,
+}
+
+fn main() {
+    let s = String::from("Ok");
+    let t = vec![4; 10];
+
+    let clump = BunchaData { s, t, curr: 0 };
+    println!("I built {clump:?} using {s} and {t:?}");
+}